@@ -0,0 +1,156 @@
+use indicatif::ProgressBar;
+use lazy_static::lazy_static;
+use reqwest::Client;
+use structopt::StructOpt;
+
+/// Command-line arguments accepted by feroxbuster
+///
+/// Only the options exercised by `heuristics.rs`'s retry/decompression/auth/wildcard-probe logic
+/// are represented here; the rest of feroxbuster's CLI surface (wordlist, threads, extensions,
+/// ...) lives alongside this in the full project and isn't part of this slice of the tree.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "feroxbuster")]
+pub struct CliArgs {
+    /// Don't auto-filter wildcard responses
+    #[structopt(long)]
+    pub dont_filter: bool,
+
+    /// Only print URLs, suppress status codes, response sizes, etc
+    #[structopt(short, long)]
+    pub quiet: bool,
+
+    /// Output file to write results to
+    #[structopt(short, long, default_value = "")]
+    pub output: String,
+
+    /// Append / to each request
+    #[structopt(long)]
+    pub add_slash: bool,
+
+    /// Number of times to retry a request that fails at the connection level before giving up on
+    /// the target
+    #[structopt(long, default_value = "3")]
+    pub retries: u32,
+
+    /// Upper bound, in milliseconds, on the exponential backoff used between retries
+    #[structopt(long, default_value = "30000")]
+    pub retry_backoff_ceiling: u64,
+
+    /// Don't decompress gzip/deflate/br response bodies; heuristics will compare wire-size
+    /// responses instead of decoded ones
+    #[structopt(long)]
+    pub no_decompress: bool,
+
+    /// Number of unique-string lengths probed when fitting the wildcard dynamic-size model
+    #[structopt(long, default_value = "4")]
+    pub wildcard_probes: usize,
+
+    /// HTTP authentication scheme to use against each target: basic, digest, or bearer. Leave
+    /// unset to send requests unauthenticated.
+    #[structopt(long, default_value = "")]
+    pub auth_scheme: String,
+
+    /// Username for --auth-scheme basic/digest
+    #[structopt(long, default_value = "")]
+    pub auth_username: String,
+
+    /// Password for --auth-scheme basic/digest
+    #[structopt(long, default_value = "")]
+    pub auth_password: String,
+
+    /// Token for --auth-scheme bearer
+    #[structopt(long, default_value = "")]
+    pub auth_token: String,
+}
+
+/// Fully resolved configuration used throughout the scan
+pub struct Configuration {
+    /// don't auto-filter wildcard responses
+    pub dont_filter: bool,
+
+    /// suppress status codes, response sizes, etc in stdout
+    pub quiet: bool,
+
+    /// file to additionally write results to; empty means file output is disabled
+    pub output: String,
+
+    /// append / to each request
+    pub add_slash: bool,
+
+    /// query params appended to every request
+    pub queries: Vec<(String, String)>,
+
+    /// status codes that are considered a "hit" for a request
+    pub status_codes: Vec<u16>,
+
+    /// client shared by every request made during the scan
+    pub client: Client,
+
+    /// number of connection-level retries, with backoff, before a target is declared dead
+    pub retries: u32,
+
+    /// upper bound, in milliseconds, on the backoff used between retries
+    pub retry_backoff_ceiling: u64,
+
+    /// skip transparent decompression of gzip/deflate/br bodies
+    pub no_decompress: bool,
+
+    /// number of unique-string lengths probed when fitting the wildcard dynamic-size model
+    pub wildcard_probes: usize,
+
+    /// HTTP authentication scheme to negotiate against each target ("basic", "digest",
+    /// "bearer"), or empty to send requests unauthenticated
+    pub auth_scheme: String,
+
+    /// username for basic/digest auth
+    pub auth_username: String,
+
+    /// password for basic/digest auth
+    pub auth_password: String,
+
+    /// token for bearer auth
+    pub auth_token: String,
+}
+
+impl Configuration {
+    /// Parses CLI args and builds the client shared by every request made during the scan
+    pub fn new() -> Self {
+        let args = CliArgs::from_args();
+
+        // negotiated transparently by reqwest: unless --no-decompress is set, every response body
+        // is decoded before heuristics ever see it, so content_length() always reflects the real,
+        // decoded size rather than whatever the server happened to compress a random UUID down to
+        let client = Client::builder()
+            .gzip(!args.no_decompress)
+            .deflate(!args.no_decompress)
+            .brotli(!args.no_decompress)
+            .build()
+            .expect("Could not build reqwest client");
+
+        Configuration {
+            dont_filter: args.dont_filter,
+            quiet: args.quiet,
+            output: args.output,
+            add_slash: args.add_slash,
+            queries: vec![],
+            status_codes: vec![200, 204, 301, 302, 307, 308, 401, 403, 405],
+            client,
+            retries: args.retries,
+            retry_backoff_ceiling: args.retry_backoff_ceiling,
+            no_decompress: args.no_decompress,
+            wildcard_probes: args.wildcard_probes,
+            auth_scheme: args.auth_scheme,
+            auth_username: args.auth_username,
+            auth_password: args.auth_password,
+            auth_token: args.auth_token,
+        }
+    }
+}
+
+lazy_static! {
+    /// global, read-only configuration for the running scan
+    pub static ref CONFIGURATION: Configuration = Configuration::new();
+
+    /// progress bar shared across heuristics output
+    pub static ref PROGRESS_PRINTER: ProgressBar = ProgressBar::new(0);
+}
@@ -7,12 +7,479 @@ use crate::utils::{
 use crate::FeroxResponse;
 use console::style;
 use indicatif::ProgressBar;
+use lazy_static::lazy_static;
+use rand::Rng;
+use reqwest::header::{
+    HeaderMap, HeaderValue, ACCEPT_ENCODING, AUTHORIZATION, PROXY_AUTHENTICATE, WWW_AUTHENTICATE,
+};
+use reqwest::{Client, Response, StatusCode, Url};
+use std::collections::{HashMap, VecDeque};
 use std::process;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::time::sleep;
 use uuid::Uuid;
 
-/// length of a standard UUID, used when determining wildcard responses
-const UUID_LENGTH: u64 = 32;
+/// base backoff duration (in milliseconds), doubled on every retry attempt and ultimately capped
+/// at `CONFIGURATION.retry_backoff_ceiling`
+const RETRY_BASE_BACKOFF_MILLIS: u64 = 250;
+
+/// Forces wire-size bodies on wildcard probes when `--no-decompress` is set
+///
+/// `CONFIGURATION.client` is built with gzip/deflate/br negotiation enabled, so by default it
+/// transparently decodes every response body before `content_length()` is computed - nothing
+/// extra is needed here, and manually setting `Accept-Encoding` on every request would instead
+/// suppress reqwest's own automatic decompression (the opposite of what's wanted). The one case
+/// that does need an explicit header is `--no-decompress`: asking for `identity` tells the server
+/// not to compress the body at all, so users who specifically want to match on wire size get it.
+fn no_decompress_header() -> Option<HeaderMap> {
+    if !CONFIGURATION.no_decompress {
+        return None;
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("identity"));
+
+    Some(headers)
+}
+
+/// Owns the terminal and file-output ends of heuristics reporting and keeps messages in order
+///
+/// Previously every heuristics message was `ferox_print`ed to the terminal and separately shipped
+/// to the file handler through its own cloned `UnboundedSender`, so the two paths could interleave
+/// out of order. `ReportSink` buffers messages in a single queue instead: `emit` appends to the
+/// back, `send_before` jumps the queue so a summary can be guaranteed to print ahead of the detail
+/// lines that produced it, and `flush` drains the queue in order. Nothing here ever `.await`s, so
+/// calling it from the middle of the scan loop never stalls it.
+pub struct ReportSink {
+    /// progress bar shared with the rest of the scan for this target
+    bar: ProgressBar,
+
+    /// transmitter side of the channel that ships lines to the file-output handler
+    tx_file: UnboundedSender<String>,
+
+    /// messages waiting to be printed/shipped, in the order they should be flushed
+    queue: Mutex<VecDeque<String>>,
+}
+
+impl ReportSink {
+    /// Create a new `ReportSink` around the given progress bar and file-output channel
+    pub fn new(bar: ProgressBar, tx_file: UnboundedSender<String>) -> Self {
+        ReportSink {
+            bar,
+            tx_file,
+            queue: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// progress bar accessor, so callers can still report per-probe progress
+    pub fn bar(&self) -> &ProgressBar {
+        &self.bar
+    }
+
+    /// Queue `msg` to be written after anything already buffered
+    pub fn emit(&self, msg: String) {
+        self.queue.lock().unwrap().push_back(msg);
+    }
+
+    /// Queue `msg` ahead of anything already buffered
+    pub fn send_before(&self, msg: String) {
+        self.queue.lock().unwrap().push_front(msg);
+    }
+
+    /// Drain the queue in order, printing each message to the terminal and shipping it to the
+    /// file-output handler
+    pub fn flush(&self) {
+        let mut queue = self.queue.lock().unwrap();
+
+        while let Some(msg) = queue.pop_front() {
+            ferox_print(&msg, &PROGRESS_PRINTER);
+
+            try_send_message_to_file(&msg, self.tx_file.clone(), !CONFIGURATION.output.is_empty());
+        }
+    }
+}
+
+lazy_static! {
+    /// authenticators negotiated from `CONFIGURATION`'s auth scheme/credentials, keyed by target
+    /// origin (scheme://host:port). Each target gets its own instance so that one target's
+    /// observed realm/nonce/credential state is never replayed against a different target; within
+    /// a single target, every request shares the same instance so a challenge only needs to be
+    /// handshaked once.
+    static ref AUTHENTICATORS: Mutex<HashMap<String, Arc<dyn Authenticator>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// A single challenge/response authentication scheme
+///
+/// `connectivity_test` triggers the initial handshake with its first request, which carries
+/// whatever `authorization` can already produce (nothing, for digest before any challenge);
+/// when the target answers with a `401`/`407`, `observe_challenge` is given the
+/// `WWW-Authenticate`/`Proxy-Authenticate` value and caches whatever state (realm, nonce, token,
+/// ...) it needs, and `authorization` is then consulted on every subsequent request (including
+/// the immediate replay) to produce the `Authorization` header.
+pub trait Authenticator: Send + Sync {
+    /// Parse a challenge header and cache the state needed to answer it
+    fn observe_challenge(&self, challenge: &str, method: &str, url: &Url);
+
+    /// Build the `Authorization` header value for the given request, using whatever state was
+    /// cached from the last observed challenge. Returns `None` if no credentials can be computed
+    /// yet (e.g. a digest scheme that hasn't seen a challenge).
+    fn authorization(&self, method: &str, url: &Url) -> Option<String>;
+}
+
+/// `Authorization: Basic <base64(username:password)>`, sent unconditionally once configured
+struct BasicAuthenticator {
+    header_value: String,
+}
+
+impl BasicAuthenticator {
+    fn new(username: &str, password: &str) -> Self {
+        BasicAuthenticator {
+            header_value: format!(
+                "Basic {}",
+                base64::encode(format!("{}:{}", username, password))
+            ),
+        }
+    }
+}
+
+impl Authenticator for BasicAuthenticator {
+    fn observe_challenge(&self, _challenge: &str, _method: &str, _url: &Url) {
+        // basic auth doesn't carry any server-provided state; credentials are sent unconditionally
+    }
+
+    fn authorization(&self, _method: &str, _url: &Url) -> Option<String> {
+        Some(self.header_value.clone())
+    }
+}
+
+/// `Authorization: Bearer <token>`, with a hook to refresh the cached token once a challenge is
+/// observed (`new` installs a no-op hook that keeps the current token; no other constructor or
+/// setter is provided)
+struct BearerAuthenticator {
+    token: Mutex<String>,
+    refresh: Box<dyn Fn(&str) -> String + Send + Sync>,
+}
+
+impl BearerAuthenticator {
+    fn new(token: &str) -> Self {
+        BearerAuthenticator {
+            token: Mutex::new(token.to_owned()),
+            refresh: Box::new(|current| current.to_owned()),
+        }
+    }
+}
+
+impl Authenticator for BearerAuthenticator {
+    fn observe_challenge(&self, _challenge: &str, _method: &str, _url: &Url) {
+        let mut token = self.token.lock().unwrap();
+        *token = (self.refresh)(&token);
+    }
+
+    fn authorization(&self, _method: &str, _url: &Url) -> Option<String> {
+        Some(format!("Bearer {}", self.token.lock().unwrap()))
+    }
+}
+
+/// State cached between a digest challenge and the requests that answer it
+#[derive(Default)]
+struct DigestState {
+    realm: String,
+    nonce: String,
+
+    /// the single qop token to use, or `None` when the challenge didn't offer one at all (RFC
+    /// 2069 digest auth, as opposed to RFC 2617's qop-protected variant)
+    qop: Option<String>,
+    opaque: Option<String>,
+    nonce_count: u32,
+}
+
+/// RFC 2617 digest auth; waits for a challenge before it can compute an `Authorization` header
+struct DigestAuthenticator {
+    username: String,
+    password: String,
+    state: Mutex<Option<DigestState>>,
+}
+
+impl DigestAuthenticator {
+    fn new(username: &str, password: &str) -> Self {
+        DigestAuthenticator {
+            username: username.to_owned(),
+            password: password.to_owned(),
+            state: Mutex::new(None),
+        }
+    }
+}
+
+impl Authenticator for DigestAuthenticator {
+    fn observe_challenge(&self, challenge: &str, _method: &str, _url: &Url) {
+        let params = parse_challenge_params(challenge);
+
+        let mut state = self.state.lock().unwrap();
+        *state = Some(DigestState {
+            realm: params.get("realm").cloned().unwrap_or_default(),
+            nonce: params.get("nonce").cloned().unwrap_or_default(),
+            qop: params.get("qop").map(|raw| select_qop(raw)),
+            opaque: params.get("opaque").cloned(),
+            nonce_count: 0,
+        });
+    }
+
+    fn authorization(&self, method: &str, url: &Url) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        let state = state.as_mut()?;
+
+        let uri = url.path();
+
+        let ha1 = format!(
+            "{:x}",
+            md5::compute(format!("{}:{}:{}", self.username, state.realm, self.password))
+        );
+        let ha2 = format!("{:x}", md5::compute(format!("{}:{}", method, uri)));
+
+        let mut header = match &state.qop {
+            Some(qop) => {
+                // RFC 2617: qop-protected digest, nc/cnonce are part of the response hash and
+                // must be sent alongside it
+                state.nonce_count += 1;
+
+                let nc = format!("{:08x}", state.nonce_count);
+                let cnonce = unique_string(1)[..16].to_string();
+
+                let response = format!(
+                    "{:x}",
+                    md5::compute(format!(
+                        "{}:{}:{}:{}:{}:{}",
+                        ha1, state.nonce, nc, cnonce, qop, ha2
+                    ))
+                );
+
+                format!(
+                    "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", qop={}, nc={}, cnonce=\"{}\", response=\"{}\"",
+                    self.username, state.realm, state.nonce, uri, qop, nc, cnonce, response
+                )
+            }
+            None => {
+                // RFC 2069: no qop was offered, so nc/cnonce aren't part of the protocol at all;
+                // the response hash is computed directly from ha1:nonce:ha2
+                let response = format!(
+                    "{:x}",
+                    md5::compute(format!("{}:{}:{}", ha1, state.nonce, ha2))
+                );
+
+                format!(
+                    "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", response=\"{}\"",
+                    self.username, state.realm, state.nonce, uri, response
+                )
+            }
+        };
+
+        if let Some(opaque) = &state.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+
+        Some(header)
+    }
+}
+
+/// Picks a single qop token out of a (possibly comma-separated) challenge `qop` value, e.g.
+/// `"auth,auth-int"`. This client only ever computes ha2 the "auth" way (`H(method:uri)`), so
+/// "auth" is preferred whenever the server offers it.
+fn select_qop(raw: &str) -> String {
+    let tokens: Vec<&str> = raw.split(',').map(str::trim).collect();
+
+    if tokens.iter().any(|token| *token == "auth") {
+        "auth".to_string()
+    } else {
+        tokens.first().copied().unwrap_or("auth").to_string()
+    }
+}
+
+/// Parses a `WWW-Authenticate`/`Proxy-Authenticate` challenge's `key="value"` pairs into a map,
+/// ignoring the leading scheme token (e.g. `Digest`). Commas inside quoted values (e.g.
+/// `qop="auth,auth-int"`) are not treated as pair separators.
+fn parse_challenge_params(challenge: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+
+    let rest = challenge.splitn(2, ' ').nth(1).unwrap_or("");
+
+    let mut pairs = vec![];
+    let mut pair_start = 0;
+    let mut in_quotes = false;
+
+    for (i, c) in rest.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                pairs.push(&rest[pair_start..i]);
+                pair_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    pairs.push(&rest[pair_start..]);
+
+    for pair in pairs {
+        if let Some(idx) = pair.find('=') {
+            let (key, value) = pair.split_at(idx);
+            let value = &value[1..];
+            params.insert(
+                key.trim().to_string(),
+                value.trim().trim_matches('"').to_string(),
+            );
+        }
+    }
+
+    params
+}
+
+/// Reads `CONFIGURATION`'s auth scheme/credential fields and builds the corresponding
+/// [`Authenticator`], or `None` if no scheme was configured
+fn build_authenticator() -> Option<Arc<dyn Authenticator>> {
+    match CONFIGURATION.auth_scheme.to_lowercase().as_str() {
+        "basic" => Some(Arc::new(BasicAuthenticator::new(
+            &CONFIGURATION.auth_username,
+            &CONFIGURATION.auth_password,
+        ))),
+        "digest" => Some(Arc::new(DigestAuthenticator::new(
+            &CONFIGURATION.auth_username,
+            &CONFIGURATION.auth_password,
+        ))),
+        "bearer" => Some(Arc::new(BearerAuthenticator::new(&CONFIGURATION.auth_token))),
+        _ => None,
+    }
+}
+
+/// Returns the [`Authenticator`] cached for `url`'s origin, building and caching a fresh one the
+/// first time a given target is seen. Returns `None` if no auth scheme is configured.
+fn authenticator_for(url: &Url) -> Option<Arc<dyn Authenticator>> {
+    if CONFIGURATION.auth_scheme.is_empty() {
+        return None;
+    }
+
+    let key = url.origin().ascii_serialization();
+    let mut authenticators = AUTHENTICATORS.lock().unwrap();
+
+    if let Some(existing) = authenticators.get(&key) {
+        return Some(existing.clone());
+    }
+
+    let authenticator = build_authenticator()?;
+    authenticators.insert(key, authenticator.clone());
+    Some(authenticator)
+}
+
+/// Adds the negotiated `Authorization` header (if an authenticator is configured and has enough
+/// cached state to compute one) to `headers`
+fn add_authorization(headers: &mut HeaderMap, method: &str, url: &Url) {
+    if let Some(authenticator) = authenticator_for(url) {
+        if let Some(value) = authenticator.authorization(method, url) {
+            if let Ok(header_value) = HeaderValue::from_str(&value) {
+                headers.insert(AUTHORIZATION, header_value);
+            }
+        }
+    }
+}
+
+/// Pulls the `WWW-Authenticate`/`Proxy-Authenticate` challenge out of a `401`/`407` response
+fn challenge_header(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(WWW_AUTHENTICATE)
+        .or_else(|| response.headers().get(PROXY_AUTHENTICATE))
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+/// minimum R² for a fitted wildcard model to be trusted; below this, the server's responses are
+/// considered too noisy to safely auto-filter
+const MIN_FIT_R_SQUARED: f64 = 0.9;
+
+/// how close a fitted `slope` must be to 1.0 (content reflects the url, i.e. dynamic) or 0.0
+/// (content is constant, i.e. static) to be classified as such
+const SLOPE_TOLERANCE: f64 = 0.1;
+
+/// if every probe's content length falls within this many bytes of the mean, the server is
+/// treated as static outright, regardless of R². A handful of near-identical lengths have almost
+/// no variance to explain, which makes R² unstable (or even negative) even for a genuinely clean
+/// fit; the baseline exact-equality check this replaced didn't have that problem, so this keeps
+/// that case working.
+const MAX_STATIC_RESIDUAL_BYTES: f64 = 8.0;
+
+/// Result of fitting `content_length ~= slope * url_path_length + intercept` across a set of
+/// `(url_path_length, content_length)` probes
+struct LinearFit {
+    /// rate of change of content length per character of url path length
+    slope: f64,
+
+    /// content length at a (hypothetical) url path length of zero
+    intercept: f64,
+
+    /// coefficient of determination; how well the line explains the observed points, 1.0 is a
+    /// perfect fit
+    r_squared: f64,
+
+    /// largest absolute distance from the mean content length seen across all probes
+    max_deviation: f64,
+}
+
+/// Ordinary least-squares fit of `points` to a line, used to tell a dynamic (reflected-content)
+/// custom 404 apart from a static one, and to bail out entirely when the server's responses don't
+/// follow either pattern cleanly
+fn fit_linear_model(points: &[(u64, u64)]) -> LinearFit {
+    let n = points.len() as f64;
+
+    let mean_x = points.iter().map(|(x, _)| *x as f64).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| *y as f64).sum::<f64>() / n;
+
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+
+    for (x, y) in points {
+        let dx = *x as f64 - mean_x;
+        let dy = *y as f64 - mean_y;
+        covariance += dx * dy;
+        variance_x += dx * dx;
+    }
+
+    let slope = if variance_x > 0.0 {
+        covariance / variance_x
+    } else {
+        0.0
+    };
+    let intercept = mean_y - slope * mean_x;
+
+    let mut residual_sum_of_squares = 0.0;
+    let mut total_sum_of_squares = 0.0;
+
+    for (x, y) in points {
+        let predicted = slope * (*x as f64) + intercept;
+        let actual = *y as f64;
+        residual_sum_of_squares += (actual - predicted).powi(2);
+        total_sum_of_squares += (actual - mean_y).powi(2);
+    }
+
+    let r_squared = if total_sum_of_squares > 0.0 {
+        1.0 - (residual_sum_of_squares / total_sum_of_squares)
+    } else {
+        // every response was exactly the same length; a perfectly static fit
+        1.0
+    };
+
+    let max_deviation = points
+        .iter()
+        .map(|(_, y)| (*y as f64 - mean_y).abs())
+        .fold(0.0, f64::max);
+
+    LinearFit {
+        slope,
+        intercept,
+        r_squared,
+        max_deviation,
+    }
+}
 
 /// Simple helper to return a uuid, formatted as lowercase without hyphens
 ///
@@ -33,21 +500,127 @@ fn unique_string(length: usize) -> String {
     unique_id
 }
 
+/// Computes the backoff duration for the given (zero-indexed) retry attempt
+///
+/// The base backoff doubles on every attempt and is capped at
+/// `CONFIGURATION.retry_backoff_ceiling` milliseconds, then jittered by +/- 20% so that a bunch
+/// of concurrent requests hitting the same flaky target don't all retry in lockstep. The result
+/// is re-clamped to the ceiling after jittering, since positive jitter on an already-capped
+/// backoff would otherwise push it above the documented upper bound.
+fn backoff_with_jitter(attempt: u32) -> Duration {
+    let doubled = RETRY_BASE_BACKOFF_MILLIS.saturating_mul(1 << attempt.min(16));
+    let capped = doubled.min(CONFIGURATION.retry_backoff_ceiling);
+
+    let jitter_range = (capped as f64 * 0.2) as i64;
+    let jitter = if jitter_range > 0 {
+        rand::thread_rng().gen_range(-jitter_range..=jitter_range)
+    } else {
+        0
+    };
+
+    let millis = ((capped as i64 + jitter).max(0) as u64).min(CONFIGURATION.retry_backoff_ceiling);
+
+    Duration::from_millis(millis)
+}
+
+/// Wraps [`make_request`] with exponential backoff + jitter, retrying on connection-level errors
+///
+/// A target is only considered dead once the initial attempt and all `CONFIGURATION.retries`
+/// retries after it have failed. Every
+/// retry emits a one-line notice; when a `sink` is supplied, it's queued through that target's
+/// [`ReportSink`] so it can't interleave with that target's other buffered output. Callers with
+/// no `ReportSink` yet (namely `connectivity_test`, which runs before any per-target scan state
+/// exists) get the notice printed immediately instead.
+async fn make_request_with_retry(
+    client: &Client,
+    url: &Url,
+    headers: Option<HeaderMap>,
+    sink: Option<&ReportSink>,
+) -> Result<Response, reqwest::Error> {
+    log::trace!("enter: make_request_with_retry({:?}, {})", client, url);
+
+    let mut attempt = 0;
+    let mut replayed_for_auth = false;
+
+    loop {
+        let mut request_headers = headers.clone().unwrap_or_default();
+        add_authorization(&mut request_headers, "GET", url);
+
+        let result = if request_headers.is_empty() {
+            // make_request doesn't support per-request headers; when there's nothing extra to
+            // add (no custom headers, no cached credential state yet), keep using it so this
+            // stays the single code path for a plain request
+            make_request(client, url).await
+        } else {
+            client
+                .get(url.to_owned())
+                .headers(request_headers)
+                .send()
+                .await
+        };
+
+        match result {
+            Ok(response) => {
+                if !replayed_for_auth
+                    && (response.status() == StatusCode::UNAUTHORIZED
+                        || response.status() == StatusCode::PROXY_AUTHENTICATION_REQUIRED)
+                {
+                    if let Some(challenge) = challenge_header(&response) {
+                        if let Some(authenticator) = authenticator_for(url) {
+                            authenticator.observe_challenge(&challenge, "GET", url);
+                            replayed_for_auth = true;
+
+                            // replay immediately with the now-cached credential state; this isn't
+                            // a connection-retry attempt, so it doesn't consume the backoff budget
+                            continue;
+                        }
+                    }
+                }
+
+                log::trace!("exit: make_request_with_retry -> {:?}", response);
+                return Ok(response);
+            }
+            Err(e) => {
+                if !(e.is_connect() || e.is_timeout()) || attempt >= CONFIGURATION.retries {
+                    // only connection-level errors are transient enough to be worth retrying;
+                    // redirect-loop, decode, and body errors will just fail the same way again
+                    log::trace!("exit: make_request_with_retry -> {:?}", e);
+                    return Err(e);
+                }
+
+                let backoff = backoff_with_jitter(attempt);
+
+                if !CONFIGURATION.quiet {
+                    let msg = format!(
+                        "{} {} retrying {} in {:?} (attempt {}/{}): {}\n",
+                        status_colorizer("WLD"),
+                        module_colorizer("heuristics::make_request_with_retry"),
+                        url,
+                        backoff,
+                        attempt + 1,
+                        CONFIGURATION.retries,
+                        e
+                    );
+
+                    match sink {
+                        Some(sink) => sink.emit(msg),
+                        None => ferox_print(&msg, &PROGRESS_PRINTER),
+                    }
+                }
+
+                attempt += 1;
+                sleep(backoff).await;
+            }
+        }
+    }
+}
+
 /// Tests the given url to see if it issues a wildcard response
 ///
 /// In the event that url returns a wildcard response, a
 /// [WildcardFilter](struct.WildcardFilter.html) is created and returned to the caller.
-pub async fn wildcard_test(
-    target_url: &str,
-    bar: ProgressBar,
-    tx_file: UnboundedSender<String>,
-) -> Option<WildcardFilter> {
-    log::trace!(
-        "enter: wildcard_test({:?}, {:?}, {:?})",
-        target_url,
-        bar,
-        tx_file
-    );
+pub async fn wildcard_test(target_url: &str, sink: &ReportSink) -> Option<WildcardFilter> {
+    log::trace!("enter: wildcard_test({:?}, {:?})", target_url, sink.bar());
 
     if CONFIGURATION.dont_filter {
         // early return, dont_filter scans don't need tested
@@ -55,84 +628,142 @@ pub async fn wildcard_test(
         return None;
     }
 
-    let clone_req_one = tx_file.clone();
-    let clone_req_two = tx_file.clone();
+    // number of unique-string lengths to probe; more probes make for a more reliable fit at the
+    // cost of additional requests per target
+    let probe_count = CONFIGURATION.wildcard_probes.max(2);
 
-    if let Some(ferox_response) = make_wildcard_request(&target_url, 1, clone_req_one).await {
-        bar.inc(1);
+    if let Some(first_response) = make_wildcard_request(&target_url, 1, sink).await {
+        sink.bar().inc(1);
 
         // found a wildcard response
         let mut wildcard = WildcardFilter::default();
 
-        let wc_length = ferox_response.content_length();
+        let first_length = first_response.content_length();
 
-        if wc_length == 0 {
+        if first_length == 0 {
+            sink.flush();
             log::trace!("exit: wildcard_test -> Some({:?})", wildcard);
             return Some(wildcard);
         }
 
-        // content length of wildcard is non-zero, perform additional tests:
-        //   make a second request, with a known-sized (64) longer request
-        if let Some(resp_two) = make_wildcard_request(&target_url, 3, clone_req_two).await {
-            bar.inc(1);
+        // content length of the first probe is non-zero; gather more probes at increasing
+        // unique-string lengths and fit a line across (url_path_length, content_length)
+        let mut points = vec![(get_url_path_length(&first_response.url()), first_length)];
 
-            let wc2_length = resp_two.content_length();
+        for length in 2..=probe_count {
+            if let Some(response) = make_wildcard_request(&target_url, length, sink).await {
+                sink.bar().inc(1);
+                points.push((get_url_path_length(&response.url()), response.content_length()));
+            } else {
+                sink.bar().inc((probe_count - length + 1) as u64);
+                break;
+            }
+        }
 
-            if wc2_length == wc_length + (UUID_LENGTH * 2) {
-                // second length is what we'd expect to see if the requested url is
-                // reflected in the response along with some static content; aka custom 404
-                let url_len = get_url_path_length(&ferox_response.url());
+        if points.len() >= 2 {
+            let fit = fit_linear_model(&points);
+
+            if fit.max_deviation <= MAX_STATIC_RESIDUAL_BYTES {
+                // every probe landed within a few bytes of the mean; treat this as static outright
+                // rather than trusting R², which is unstable (or even negative) when there's
+                // almost no variance for the line to explain
+                wildcard.size = fit.intercept.max(0.0) as u64;
+
+                if !CONFIGURATION.quiet {
+                    let msg = format!(
+                        "{} {:>10} Wildcard response is static; {} {} (R\u{b2} {:.2}) responses; toggle this behavior by using {}\n",
+                        status_colorizer("WLD"),
+                        wildcard.size,
+                        style("auto-filtering").yellow(),
+                        style(wildcard.size).cyan(),
+                        fit.r_squared,
+                        style("--dont-filter").yellow()
+                    );
 
-                wildcard.dynamic = wc_length - url_len;
+                    sink.send_before(msg);
+                }
+            } else if fit.r_squared < MIN_FIT_R_SQUARED {
+                // the server's responses don't follow a clean line; don't risk auto-filtering
+                // against a model that doesn't actually describe its behavior
+                if !CONFIGURATION.quiet {
+                    let msg = format!(
+                        "{} {:>10} Wildcard response for {} didn't fit a clean model across {} probes (slope {:.2}, R\u{b2} {:.2}); leaving auto-filtering off\n",
+                        status_colorizer("WLD"),
+                        points.len(),
+                        target_url,
+                        points.len(),
+                        fit.slope,
+                        fit.r_squared
+                    );
+
+                    sink.send_before(msg);
+                }
+            } else if (fit.slope - 1.0).abs() <= SLOPE_TOLERANCE {
+                // content length grows about 1-to-1 with url length; the requested path is being
+                // reflected back in an otherwise-static custom 404
+                wildcard.dynamic = fit.intercept.max(0.0) as u64;
 
                 if !CONFIGURATION.quiet {
                     let msg = format!(
-                            "{} {:>10} Wildcard response is dynamic; {} ({} + url length) responses; toggle this behavior by using {}\n",
+                            "{} {:>10} Wildcard response is dynamic; {} ({} + url length, slope {:.2}, R\u{b2} {:.2}) responses; toggle this behavior by using {}\n",
                             status_colorizer("WLD"),
                             wildcard.dynamic,
                             style("auto-filtering").yellow(),
-                            style(wc_length - url_len).cyan(),
+                            style(wildcard.dynamic).cyan(),
+                            fit.slope,
+                            fit.r_squared,
                             style("--dont-filter").yellow()
                         );
 
-                    ferox_print(&msg, &PROGRESS_PRINTER);
-
-                    try_send_message_to_file(
-                        &msg,
-                        tx_file.clone(),
-                        !CONFIGURATION.output.is_empty(),
-                    );
+                    // guarantee the summary prints ahead of the per-probe lines already queued
+                    sink.send_before(msg);
                 }
-            } else if wc_length == wc2_length {
-                wildcard.size = wc_length;
+            } else if fit.slope.abs() <= SLOPE_TOLERANCE {
+                // content length doesn't move with url length; a plain static custom 404
+                wildcard.size = fit.intercept.max(0.0) as u64;
 
                 if !CONFIGURATION.quiet {
                     let msg = format!(
-                        "{} {:>10} Wildcard response is static; {} {} responses; toggle this behavior by using {}\n",
+                        "{} {:>10} Wildcard response is static; {} {} (R\u{b2} {:.2}) responses; toggle this behavior by using {}\n",
                         status_colorizer("WLD"),
-                        wc_length,
+                        wildcard.size,
                         style("auto-filtering").yellow(),
-                        style(wc_length).cyan(),
+                        style(wildcard.size).cyan(),
+                        fit.r_squared,
                         style("--dont-filter").yellow()
                     );
 
-                    ferox_print(&msg, &PROGRESS_PRINTER);
-
-                    try_send_message_to_file(
-                        &msg,
-                        tx_file.clone(),
-                        !CONFIGURATION.output.is_empty(),
+                    sink.send_before(msg);
+                }
+            } else {
+                // the fit is clean but the slope matches neither pattern this model knows about
+                // (e.g. slope ~2 from the url being reflected more than once in the body). Only
+                // single-reflection dynamic 404s are auto-filtered today; rather than guess at a
+                // reflection count that downstream filtering doesn't support, leave auto-filtering
+                // off and say why instead of silently doing nothing
+                if !CONFIGURATION.quiet {
+                    let msg = format!(
+                        "{} {:>10} Wildcard response for {} fit a line (slope {:.2}, R\u{b2} {:.2}) that isn't a recognized static or single-reflection pattern; leaving auto-filtering off\n",
+                        status_colorizer("WLD"),
+                        points.len(),
+                        target_url,
+                        fit.slope,
+                        fit.r_squared
                     );
+
+                    sink.send_before(msg);
                 }
             }
-        } else {
-            bar.inc(2);
         }
 
+        sink.flush();
+
         log::trace!("exit: wildcard_test -> Some({:?})", wildcard);
         return Some(wildcard);
     }
 
+    sink.flush();
+
     log::trace!("exit: wildcard_test -> None");
     None
 }
@@ -146,13 +777,13 @@ pub async fn wildcard_test(
 async fn make_wildcard_request(
     target_url: &str,
     length: usize,
-    tx_file: UnboundedSender<String>,
+    sink: &ReportSink,
 ) -> Option<FeroxResponse> {
     log::trace!(
         "enter: make_wildcard_request({}, {}, {:?})",
         target_url,
         length,
-        tx_file
+        sink.bar()
     );
 
     let unique_str = unique_string(length);
@@ -174,7 +805,14 @@ async fn make_wildcard_request(
 
     let wildcard = status_colorizer("WLD");
 
-    match make_request(&CONFIGURATION.client, &nonexistent.to_owned()).await {
+    match make_request_with_retry(
+        &CONFIGURATION.client,
+        &nonexistent.to_owned(),
+        no_decompress_header(),
+        Some(sink),
+    )
+    .await
+    {
         Ok(response) => {
             if CONFIGURATION
                 .status_codes
@@ -195,13 +833,7 @@ async fn make_wildcard_request(
                         url_len
                     );
 
-                    ferox_print(&msg, &PROGRESS_PRINTER);
-
-                    try_send_message_to_file(
-                        &msg,
-                        tx_file.clone(),
-                        !CONFIGURATION.output.is_empty(),
-                    );
+                    sink.emit(msg);
                 }
 
                 if ferox_response.status().is_redirection() {
@@ -217,13 +849,7 @@ async fn make_wildcard_request(
                                 next_loc_str
                             );
 
-                            ferox_print(&msg, &PROGRESS_PRINTER);
-
-                            try_send_message_to_file(
-                                &msg,
-                                tx_file.clone(),
-                                !CONFIGURATION.output.is_empty(),
-                            );
+                            sink.emit(msg);
                         }
                     }
                 }
@@ -243,7 +869,15 @@ async fn make_wildcard_request(
 
 /// Simply tries to connect to all given sites before starting to scan
 ///
-/// In the event that no sites can be reached, the program will exit.
+/// Each target is given `CONFIGURATION.retries` chances (with exponential backoff + jitter
+/// between attempts) to recover from a transient connection error before being dropped. In the
+/// event that no sites can be reached, the program will exit.
+///
+/// If `CONFIGURATION.auth_scheme` selects an authentication scheme, this first request against
+/// each target also performs the full challenge/response handshake (see [`Authenticator`]); the
+/// resulting credential state is cached per target and reused by the later requests made through
+/// this module (the wildcard probes), so they don't need to re-handshake. The cache is private
+/// to this module; requests made elsewhere do not consult it.
 ///
 /// Any urls that are found to be alive are returned to the caller.
 pub async fn connectivity_test(target_urls: &[String]) -> Vec<String> {
@@ -266,7 +900,7 @@ pub async fn connectivity_test(target_urls: &[String]) -> Vec<String> {
             }
         };
 
-        match make_request(&CONFIGURATION.client, &request).await {
+        match make_request_with_retry(&CONFIGURATION.client, &request, None, None).await {
             Ok(_) => {
                 good_urls.push(target_url.to_owned());
             }
@@ -346,6 +980,187 @@ mod tests {
         assert_eq!(wcf.dynamic, 0);
     }
 
+    #[test]
+    /// a perfectly reflected custom 404 (content length == url length + constant) should fit a
+    /// slope of ~1.0 with a clean R²
+    fn heuristics_fit_linear_model_detects_dynamic_reflection() {
+        let points = vec![(10, 110), (20, 120), (30, 130), (40, 140)];
+        let fit = fit_linear_model(&points);
+
+        assert!((fit.slope - 1.0).abs() < 0.01);
+        assert!((fit.intercept - 100.0).abs() < 0.01);
+        assert!(fit.r_squared > 0.99);
+    }
+
+    #[test]
+    /// a constant-size custom 404 should fit a slope of ~0.0 with a clean R²
+    fn heuristics_fit_linear_model_detects_static_response() {
+        let points = vec![(10, 500), (20, 500), (30, 500), (40, 500)];
+        let fit = fit_linear_model(&points);
+
+        assert!(fit.slope.abs() < 0.01);
+        assert!((fit.intercept - 500.0).abs() < 0.01);
+        assert!(fit.r_squared > 0.99);
+    }
+
+    #[test]
+    /// noisy, non-linear responses should produce a poor R², signaling "don't auto-filter"
+    fn heuristics_fit_linear_model_flags_noisy_data() {
+        let points = vec![(10, 500), (20, 80), (30, 610), (40, 25)];
+        let fit = fit_linear_model(&points);
+
+        assert!(fit.r_squared < MIN_FIT_R_SQUARED);
+    }
+
+    #[test]
+    /// a near-static server with a few bytes of jitter has almost no variance for R² to explain,
+    /// so R² alone can't be trusted here; max_deviation should stay small enough for the
+    /// absolute-residual fallback in wildcard_test to classify it as static anyway
+    fn heuristics_fit_linear_model_reports_small_max_deviation_for_near_static_jitter() {
+        let points = vec![(10, 500), (20, 503), (30, 498), (40, 501)];
+        let fit = fit_linear_model(&points);
+
+        assert!(fit.max_deviation <= MAX_STATIC_RESIDUAL_BYTES);
+    }
+
+    #[test]
+    /// backoff should never exceed the configured ceiling, whatever the attempt number
+    fn heuristics_backoff_with_jitter_respects_ceiling() {
+        for attempt in 0..10 {
+            let backoff = backoff_with_jitter(attempt);
+            assert!(backoff.as_millis() <= CONFIGURATION.retry_backoff_ceiling as u128);
+        }
+    }
+
+    #[test]
+    /// challenge params should be parsed out of a realistic Digest challenge, ignoring the
+    /// leading scheme token
+    fn heuristics_parse_challenge_params_parses_digest_challenge() {
+        let challenge =
+            r#"Digest realm="testrealm@host.com", qop="auth", nonce="abc123", opaque="xyz789""#;
+        let params = parse_challenge_params(challenge);
+
+        assert_eq!(params.get("realm").unwrap(), "testrealm@host.com");
+        assert_eq!(params.get("qop").unwrap(), "auth");
+        assert_eq!(params.get("nonce").unwrap(), "abc123");
+        assert_eq!(params.get("opaque").unwrap(), "xyz789");
+    }
+
+    #[test]
+    /// a qop value listing multiple tokens (quoted, so its comma isn't a pair separator) should
+    /// parse as one opaque value, and select_qop should prefer "auth" out of it
+    fn heuristics_parse_challenge_params_keeps_quoted_qop_list_intact() {
+        let challenge = r#"Digest realm="test", qop="auth,auth-int", nonce="abc123""#;
+        let params = parse_challenge_params(challenge);
+
+        assert_eq!(params.get("qop").unwrap(), "auth,auth-int");
+        assert_eq!(select_qop(params.get("qop").unwrap()), "auth");
+    }
+
+    #[test]
+    /// RFC 2069 digest challenges don't carry a qop at all; the computed header must omit
+    /// qop/nc/cnonce rather than emitting them empty or malformed
+    fn heuristics_digest_authenticator_omits_qop_when_not_challenged() {
+        let auth = DigestAuthenticator::new("user", "pass");
+        let url = Url::parse("http://example.com/secret").unwrap();
+
+        auth.observe_challenge(r#"Digest realm="test", nonce="abc123""#, "GET", &url);
+
+        let header = auth.authorization("GET", &url).unwrap();
+        assert!(header.starts_with("Digest "));
+        assert!(!header.contains("qop="));
+        assert!(!header.contains("nc="));
+        assert!(!header.contains("cnonce="));
+    }
+
+    #[test]
+    /// basic auth should always produce the same base64-encoded header, challenge or not
+    fn heuristics_basic_authenticator_produces_stable_header() {
+        let auth = BasicAuthenticator::new("user", "pass");
+        let url = Url::parse("http://example.com/").unwrap();
+
+        assert_eq!(
+            auth.authorization("GET", &url).unwrap(),
+            format!("Basic {}", base64::encode("user:pass"))
+        );
+    }
+
+    #[test]
+    /// digest auth has no credentials to offer until a challenge has been observed
+    fn heuristics_digest_authenticator_requires_challenge_first() {
+        let auth = DigestAuthenticator::new("user", "pass");
+        let url = Url::parse("http://example.com/secret").unwrap();
+
+        assert!(auth.authorization("GET", &url).is_none());
+
+        auth.observe_challenge(
+            r#"Digest realm="test", qop="auth", nonce="abc123""#,
+            "GET",
+            &url,
+        );
+
+        let header = auth.authorization("GET", &url).unwrap();
+        assert!(header.starts_with("Digest "));
+        assert!(header.contains("nonce=\"abc123\""));
+        assert!(header.contains("nc=00000001"));
+    }
+
+    #[test]
+    /// no_decompress_header should only force identity encoding when --no-decompress is set; the
+    /// default path must not touch Accept-Encoding and let reqwest negotiate it
+    fn heuristics_no_decompress_header_only_set_when_requested() {
+        let headers = no_decompress_header();
+
+        if CONFIGURATION.no_decompress {
+            let value = headers.unwrap();
+            assert_eq!(
+                value.get(ACCEPT_ENCODING).unwrap().to_str().unwrap(),
+                "identity"
+            );
+        } else {
+            assert!(headers.is_none());
+        }
+    }
+
+    #[test]
+    /// authenticator_for should cache one instance per origin; requests against a different
+    /// target must never see another target's cached challenge state
+    fn heuristics_authenticator_for_is_cached_per_target() {
+        if CONFIGURATION.auth_scheme.is_empty() {
+            // no scheme configured in this run; authenticator_for always returns None, nothing to
+            // assert about caching
+            return;
+        }
+
+        let first = Url::parse("http://first.example.com/").unwrap();
+        let second = Url::parse("http://second.example.com/").unwrap();
+
+        let a = authenticator_for(&first).unwrap();
+        let b = authenticator_for(&first).unwrap();
+        let c = authenticator_for(&second).unwrap();
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+    }
+
+    #[test]
+    /// send_before should jump the queue ahead of anything already emitted
+    fn heuristics_report_sink_send_before_jumps_queue() {
+        let (tx, _rx): FeroxChannel<String> = mpsc::unbounded_channel();
+        let sink = ReportSink::new(ProgressBar::hidden(), tx);
+
+        sink.emit("detail one".to_string());
+        sink.emit("detail two".to_string());
+        sink.send_before("summary".to_string());
+
+        let queue = sink.queue.lock().unwrap();
+
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue[0], "summary");
+        assert_eq!(queue[1], "detail one");
+        assert_eq!(queue[2], "detail two");
+    }
+
     #[tokio::test(core_threads = 1)]
     /// tests that given a message and transmitter, the function sends the message across the
     /// channel